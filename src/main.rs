@@ -1,19 +1,28 @@
 use chrono::Local;
 use futures_util::StreamExt;
-use input::event::EventTrait;
 use input::event::keyboard::KeyboardEventTrait;
-use input::{Event as LibinputEvent, Libinput, LibinputInterface};
-use std::collections::HashMap;
+use input::event::{DeviceEvent, EventTrait};
+use input::{DeviceCapability, Event as LibinputEvent, Libinput, LibinputInterface};
+use input_linux::EvdevHandle;
+use std::collections::{HashMap, HashSet};
 use std::fs::OpenOptions;
 use std::os::fd::{AsRawFd, OwnedFd};
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use tokio::io::unix::AsyncFd;
-use utils::keys::Key;
-use zbus::{Message, MessageStream, connection::Builder, proxy};
+use utils::bindings::{Bindings, WindowRef};
+use utils::config::Config;
+use utils::keys::{Chord, Modifiers, key_name, modifier_of};
+use utils::uinput::VirtualKeyboard;
+use zbus::{Connection, Message, MessageStream, connection::Builder, proxy};
 
 mod utils;
 
+// Path to the TOML configuration, relative to $XDG_CONFIG_HOME (falling back
+// to ~/.config). Declares the keyboards to track and the AT-SPI event classes
+// to subscribe to.
+const CONFIG_PATH: &str = "fswitcher/config.toml";
+
 #[proxy(
     interface = "org.a11y.atspi.Registry",
     default_service = "org.a11y.atspi.Registry",
@@ -24,21 +33,93 @@ trait Registry {
     fn deregister_event(&self, event: &str) -> zbus::Result<()>;
 }
 
+// The well-known broker that hands out the real AT-SPI bus address. Querying it
+// at runtime removes any assumption about the user's uid or socket path.
+#[proxy(
+    interface = "org.a11y.Bus",
+    default_service = "org.a11y.Bus",
+    default_path = "/org/a11y/bus"
+)]
+trait A11yBus {
+    fn get_address(&self) -> zbus::Result<String>;
+}
+
+// Component interface of an accessible window; `GrabFocus` raises and focuses it.
+// The destination/path are bound per call via the proxy builder, so no defaults
+// are declared here.
+#[proxy(interface = "org.a11y.atspi.Component")]
+trait Component {
+    fn grab_focus(&self) -> zbus::Result<bool>;
+}
+
+// Accessible interface, used to resolve an object's name and walk to its owning
+// application. Destination/path are bound per call via the proxy builder.
+#[proxy(interface = "org.a11y.atspi.Accessible")]
+trait Accessible {
+    #[zbus(property)]
+    fn name(&self) -> zbus::Result<String>;
+
+    // Returns the application accessible as a `(bus_name, object_path)` pair.
+    fn get_application(&self) -> zbus::Result<(String, zbus::zvariant::OwnedObjectPath)>;
+}
+
+// Locate the configuration file under the XDG config directory.
+fn config_path() -> std::path::PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join(".config")))
+        .unwrap_or_default();
+    base.join(CONFIG_PATH)
+}
+
+// Ask the session bus for the AT-SPI bus address and open a connection to it.
+async fn connect_atspi() -> Result<Connection, Box<dyn std::error::Error>> {
+    let session = Connection::session().await?;
+    let bus = A11yBusProxy::new(&session).await?;
+    let address = bus.get_address().await?;
+    println!("(fswitcher) Discovered AT-SPI bus at {address}");
+    let conn = Builder::address(address.as_str())?.build().await?;
+    Ok(conn)
+}
+
 // Event polling keys
 // const KEY_LIBINPUT: usize = 0;
 // const KEY_DBUS: usize = 1;
 
-struct Interface;
+struct Interface {
+    // Product IDs to grab exclusively; empty when grab mode is off. The grab is
+    // released automatically when the fd is closed, including on process exit.
+    grab_products: HashSet<u32>,
+}
 
 impl LibinputInterface for Interface {
     fn open_restricted(&mut self, path: &Path, flags: i32) -> Result<OwnedFd, i32> {
-        OpenOptions::new()
+        let file = OpenOptions::new()
             .custom_flags(flags)
             .read(true)
             .write(true)
             .open(path)
-            .map(|file| file.into())
-            .map_err(|err| err.raw_os_error().unwrap_or(-1))
+            .map_err(|err| err.raw_os_error().unwrap_or(-1))?;
+
+        if !self.grab_products.is_empty() {
+            let handle = EvdevHandle::new(file.as_raw_fd());
+            if let Ok(id) = handle.device_id()
+                && self.grab_products.contains(&(id.product as u32))
+            {
+                match handle.grab(true) {
+                    Ok(()) => println!(
+                        "(fswitcher) Grabbed {} (product {})",
+                        path.display(),
+                        id.product
+                    ),
+                    Err(err) => {
+                        eprintln!("(fswitcher) Failed to grab {}: {err}", path.display())
+                    }
+                }
+            }
+        }
+
+        Ok(file.into())
     }
     fn close_restricted(&mut self, fd: OwnedFd) {
         drop(fd);
@@ -46,14 +127,24 @@ impl LibinputInterface for Interface {
 }
 
 struct KeyboardState {
-    ctrl_pressed: bool,
+    /// Modifiers currently held on this keyboard, updated on every modifier
+    /// press and release.
+    modifiers: Modifiers,
+    /// Whether the trigger chord is currently engaged (rising-edge guard so key
+    /// auto-repeat does not fire the action repeatedly).
+    triggered: bool,
+    /// In grab mode, the trigger keycode whose press was suppressed, so its
+    /// release can be suppressed too regardless of modifier-release ordering.
+    swallowed_key: Option<u32>,
     last_device_name: String,
 }
 
 impl KeyboardState {
     fn new() -> Self {
         Self {
-            ctrl_pressed: false,
+            modifiers: Modifiers::empty(),
+            triggered: false,
+            swallowed_key: None,
             last_device_name: String::new(),
         }
     }
@@ -63,24 +154,66 @@ impl KeyboardState {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("(fswitcher) Starting up...");
 
-    let mut input = Libinput::new_with_udev(Interface);
+    let config = Config::from_cfg(config_path())?;
+    let trigger = Chord::parse(&config.trigger)?;
+    let trigger_key = trigger
+        .key
+        .and_then(key_name)
+        .unwrap_or("(modifier only)");
+    println!(
+        "(fswitcher) Switch trigger: {} (modifiers {:?}, key {trigger_key})",
+        config.trigger, trigger.modifiers
+    );
+    for kb in &config.keyboards {
+        println!(
+            "(fswitcher) Tracking keyboard '{}' (vendor: {}, product: {})",
+            kb.name.as_deref().unwrap_or("?"),
+            kb.vendor,
+            kb.product
+        );
+    }
+
+    // Grab mode forwards every key except the triggering press, which it can
+    // only suppress if the trigger is an explicit key. A modifier-only trigger
+    // would force us to swallow that modifier wholesale (breaking Ctrl+C etc.),
+    // so it is rejected here.
+    if config.grab && trigger.key.is_none() {
+        return Err(format!(
+            "grab mode requires a trigger with an explicit key (e.g. 'Super+grave'), got '{}'",
+            config.trigger
+        )
+        .into());
+    }
+
+    // In grab mode we create a virtual keyboard to replay non-trigger events
+    // and grab the configured devices exclusively.
+    let virtual_keyboard = if config.grab {
+        println!("(fswitcher) Grab mode enabled; creating virtual keyboard...");
+        Some(VirtualKeyboard::new()?)
+    } else {
+        None
+    };
+    let grab_products: HashSet<u32> = if config.grab {
+        config.keyboards.iter().map(|kb| kb.product).collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut input = Libinput::new_with_udev(Interface {
+        grab_products: grab_products.clone(),
+    });
     input.udev_assign_seat("seat0").unwrap();
 
     let input_fd = AsyncFd::new(input.as_raw_fd())?;
 
-    let conn = Builder::address(
-        "unix:path=/run/user/1000/at-spi/bus,guid=562a3d8fe328266fef2aa97769175f53",
-    )?
-    .build()
-    .await?;
+    let conn = connect_atspi().await?;
 
-    // Get the AT-SPI registry proxy and register for events
+    // Get the AT-SPI registry proxy and register for the configured events
     let registry = RegistryProxy::new(&conn).await?;
     println!("(fswitcher) Registering for AT-SPI events...");
-    // Register for multiple event types to see what's available
-    registry.register_event("object").await?;
-    registry.register_event("focus").await?;
-    registry.register_event("window").await?;
+    for event in &config.events {
+        registry.register_event(event).await?;
+    }
     println!("(fswitcher) Registered for AT-SPI events");
 
     // Subscribe to AT-SPI events - listen for Object and Window events
@@ -107,21 +240,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut keyboard_states: HashMap<u32, KeyboardState> = HashMap::new();
 
-    let mut b_bindings: HashMap<u32, Option<String>> = HashMap::from([
-        (1, None),    // built-in keyboard
-        (8195, None), // external keyboard
-    ]);
+    // One window-binding slot per configured keyboard, keyed by product ID.
+    let mut b_bindings = Bindings::new(config.keyboards.iter().map(|kb| kb.product));
 
     loop {
         tokio::select! {
+            // The udev backend surfaces DeviceAdded/DeviceRemoved on this same
+            // fd, so hotplug is handled here alongside key events — no separate
+            // /dev/input watch is needed.
             guard = input_fd.readable() => {
                 let mut guard = guard?;
                 guard.clear_ready();
 
-                input.dispatch()?;
-                for event in &mut input {
-                    handle_keyboard_event(event, &mut keyboard_states);
-                }
+                drain_input(&mut input, &mut keyboard_states, &mut b_bindings, &conn, &trigger, &grab_products, virtual_keyboard.as_ref()).await?;
             }
 
             Some(msg) = stream_object.next() => {
@@ -132,53 +263,253 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             Some(msg) = stream_window.next() => {
                if let Ok(_msg) = msg {
-                   handle_dbus_message(&_msg, &mut b_bindings);
+                   handle_dbus_message(&_msg, &mut b_bindings, &conn).await;
                }
             }
 
             Some(msg) = stream_focus.next() => {
                 if let Ok(_msg) = msg {
-                    handle_dbus_message(&_msg, &mut b_bindings);
+                    handle_dbus_message(&_msg, &mut b_bindings, &conn).await;
                 }
             }
         }
     }
 }
 
-fn handle_keyboard_event(event: LibinputEvent, states: &mut HashMap<u32, KeyboardState>) {
+// Drain all pending libinput events, routing hotplug events to the device
+// handler and key events to the keyboard handler.
+async fn drain_input(
+    input: &mut Libinput,
+    states: &mut HashMap<u32, KeyboardState>,
+    bindings: &mut Bindings,
+    conn: &Connection,
+    trigger: &Chord,
+    grab_products: &HashSet<u32>,
+    virtual_keyboard: Option<&VirtualKeyboard>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    input.dispatch()?;
+    for event in &mut *input {
+        match &event {
+            LibinputEvent::Device(_) => handle_device_event(event, states, bindings),
+            _ => {
+                handle_keyboard_event(
+                    event,
+                    states,
+                    bindings,
+                    conn,
+                    trigger,
+                    grab_products,
+                    virtual_keyboard,
+                )
+                .await
+            }
+        }
+    }
+    Ok(())
+}
+
+// Raise and focus the window bound to a keyboard via AT-SPI `GrabFocus`.
+async fn switch_to(conn: &Connection, window: &WindowRef) -> zbus::Result<()> {
+    let component = ComponentProxy::builder(conn)
+        .destination(window.sender.clone())?
+        .path(window.object_path.clone())?
+        .build()
+        .await?;
+    component.grab_focus().await?;
+    Ok(())
+}
+
+// Resolve a focused accessible into a self-describing binding: read its own
+// name as the window title and walk to the owning application for the app name.
+async fn resolve_window(
+    conn: &Connection,
+    sender: String,
+    object_path: String,
+) -> zbus::Result<WindowRef> {
+    let accessible = AccessibleProxy::builder(conn)
+        .destination(sender.clone())?
+        .path(object_path.clone())?
+        .build()
+        .await?;
+    let title = accessible.name().await.unwrap_or_default();
+
+    let app_name = match accessible.get_application().await {
+        Ok((app_sender, app_path)) => {
+            let app = AccessibleProxy::builder(conn)
+                .destination(app_sender)?
+                .path(app_path.into_inner())?
+                .build()
+                .await?;
+            app.name().await.unwrap_or_default()
+        }
+        Err(_) => String::new(),
+    };
+
+    Ok(WindowRef {
+        sender,
+        object_path,
+        app_name,
+        title,
+    })
+}
+
+fn handle_device_event(
+    event: LibinputEvent,
+    states: &mut HashMap<u32, KeyboardState>,
+    bindings: &mut Bindings,
+) {
+    if let LibinputEvent::Device(device_event) = event {
+        let device = device_event.device();
+        // Only keyboards get state and a binding slot; mice, touchpads and the
+        // virtual device must not pollute the push-down queue. The virtual
+        // device is recognised by the name it is created with in uinput.rs.
+        if !device.has_capability(DeviceCapability::Keyboard)
+            || device.name() == "fswitcher virtual keyboard"
+        {
+            return;
+        }
+        let product = device.id_product();
+        match device_event {
+            DeviceEvent::Added(_) => {
+                states.entry(product).or_insert_with(KeyboardState::new);
+                bindings.add(product);
+                println!(
+                    "(fswitcher) Keyboard connected '{}' (vendor: {}, product: {})",
+                    device.name(),
+                    device.id_vendor(),
+                    product
+                );
+            }
+            DeviceEvent::Removed(_) => {
+                states.remove(&product);
+                bindings.remove(product);
+                println!(
+                    "(fswitcher) Keyboard removed '{}' (product: {})",
+                    device.name(),
+                    product
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn handle_keyboard_event(
+    event: LibinputEvent,
+    states: &mut HashMap<u32, KeyboardState>,
+    bindings: &Bindings,
+    conn: &Connection,
+    trigger: &Chord,
+    grab_products: &HashSet<u32>,
+    virtual_keyboard: Option<&VirtualKeyboard>,
+) {
     if let LibinputEvent::Keyboard(keyboard_event) = event {
         let device = keyboard_event.device();
         let device_id = device.id_product();
         let key = keyboard_event.key();
-        let is_ctrl = key == Key::LeftCtrl.key() || key == Key::RightCtrl.key();
+        let key_state = keyboard_event.key_state();
+        let modifier = modifier_of(key);
 
         let state = states.entry(device_id).or_insert_with(KeyboardState::new);
 
-        match keyboard_event.key_state() {
+        // Update the modifier set from this modifier key's press or release.
+        // The set is not re-read from the device: a missed release leaves the
+        // modifier set, and releasing one side of a left/right pair clears the
+        // bit while the other side is still held.
+        if let Some(m) = modifier {
+            match key_state {
+                input::event::keyboard::KeyState::Pressed => state.modifiers.insert(m),
+                input::event::keyboard::KeyState::Released => state.modifiers.remove(m),
+            }
+        }
+
+        let mods_ok = state.modifiers.contains(trigger.modifiers);
+        // A chord with an explicit key fires on that key's press; a modifier-only
+        // chord fires on the modifier press that completes the required set.
+        let is_trigger_key = match trigger.key {
+            Some(k) => key == k,
+            None => modifier.is_some_and(|m| trigger.modifiers.contains(m)),
+        };
+
+        // Whether this event should be suppressed from applications in grab
+        // mode. Only the triggering key's press (and its matching release) are
+        // swallowed; modifiers are always forwarded so e.g. Ctrl+C still works.
+        let mut swallow = false;
+
+        match key_state {
             input::event::keyboard::KeyState::Pressed => {
-                if is_ctrl && !state.ctrl_pressed {
-                    state.ctrl_pressed = true;
-                    state.last_device_name = device.name().to_string();
-
-                    println!(
-                        "(fswitcher) Ctrl pressed on '{}' (vendor: {}, product: {})",
-                        device.name(),
-                        device.id_vendor(),
-                        device.id_product()
-                    );
+                if is_trigger_key && mods_ok {
+                    swallow = true;
+                    state.swallowed_key = Some(key);
+                    if !state.triggered {
+                        state.triggered = true;
+                        state.last_device_name = device.name().to_string();
+
+                        println!(
+                            "(fswitcher) Trigger on '{}' (vendor: {}, product: {})",
+                            device.name(),
+                            device.id_vendor(),
+                            device_id
+                        );
+
+                        // Switch focus to the window bound to this keyboard.
+                        match bindings.get(device_id) {
+                            Some(window) => {
+                                println!("(fswitcher) Switching focus to {window}");
+                                if let Err(err) = switch_to(conn, window).await {
+                                    eprintln!(
+                                        "(fswitcher) Failed to focus {window}: {err} (window may be stale or gone)"
+                                    );
+                                }
+                            }
+                            None => {
+                                eprintln!(
+                                    "(fswitcher) No window bound to product {device_id}; nothing to switch to"
+                                );
+                            }
+                        }
+                    }
                 }
             }
             input::event::keyboard::KeyState::Released => {
-                if is_ctrl && state.ctrl_pressed {
-                    state.ctrl_pressed = false;
-                    println!("(fswitcher) Ctrl released on '{}'", state.last_device_name);
+                // Suppress the release of a key whose press we swallowed.
+                if state.swallowed_key == Some(key) {
+                    swallow = true;
+                    state.swallowed_key = None;
                 }
+                // The chord is no longer engaged once its key is lifted or the
+                // required modifiers drop below the trigger set.
+                let releases_trigger = match trigger.key {
+                    Some(k) => key == k,
+                    None => modifier.is_some_and(|m| trigger.modifiers.contains(m)),
+                };
+                if state.triggered && (releases_trigger || !mods_ok) {
+                    state.triggered = false;
+                    println!("(fswitcher) Trigger released on '{}'", state.last_device_name);
+                }
+            }
+        }
+
+        // In grab mode the device is held exclusively, so replay every event to
+        // applications except the suppressed trigger keys. Non-grabbed devices
+        // (including our own virtual keyboard) are left untouched, which also
+        // prevents a re-injection echo loop.
+        if let Some(vk) = virtual_keyboard
+            && grab_products.contains(&device_id)
+            && !swallow
+        {
+            let value = match key_state {
+                input::event::keyboard::KeyState::Pressed => 1,
+                input::event::keyboard::KeyState::Released => 0,
+            };
+            if let Err(err) = vk.emit(key as u16, value) {
+                eprintln!("(fswitcher) Failed to re-inject key {key}: {err}");
             }
         }
     }
 }
 
-fn handle_dbus_message(msg: &Message, b_bindings: &mut HashMap<u32, Option<String>>) {
+async fn handle_dbus_message(msg: &Message, b_bindings: &mut Bindings, conn: &Connection) {
     println!(
         "(fswitcher) D-Bus(a) Event: {:?} at{:?}",
         msg.header().member().unwrap().as_str(),
@@ -191,7 +522,7 @@ fn handle_dbus_message(msg: &Message, b_bindings: &mut HashMap<u32, Option<Strin
     ) {
         // Check for *either* the Focus event or the Window Activate event
         let is_focus_event = (interface.as_str() == "org.a11y.atspi.Event.Focus"
-            && member.as_str() == "Focis")
+            && member.as_str() == "Focus")
             || (interface.as_str() == "org.a11y.atspi.Event.Window"
                 && member.as_str() == "Activate")
             || (interface.as_str() == "org.a11y.atspi.Event.Window"
@@ -200,29 +531,36 @@ fn handle_dbus_message(msg: &Message, b_bindings: &mut HashMap<u32, Option<Strin
         if is_focus_event {
             println!("(fswitcher) D-Bus(a) signal: {}.{}", interface, member);
 
-            // Your existing "push-down queue" logic
-            if b_bindings.get(&1).is_some() && b_bindings.get(&8195).is_some() {
-                b_bindings.insert(8195, b_bindings.get(&1).unwrap().clone());
-                b_bindings.insert(1, Some(path.to_string()));
-            } else if b_bindings.get(&1).is_none() && b_bindings.get(&8195).is_none() {
-                b_bindings.insert(8195, Some(path.to_string()));
-            } else {
-                b_bindings.insert(1, Some(path.to_string()));
+            // Resolve the accessible to human-readable names and push it onto
+            // the front of the queue. The signal sender owns the accessible, so
+            // both it and the path are needed to address it.
+            let sender = msg
+                .header()
+                .sender()
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            let window = match resolve_window(conn, sender.clone(), path.to_string()).await {
+                Ok(window) => window,
+                Err(err) => {
+                    eprintln!("(fswitcher) Could not resolve {sender}{path}: {err}");
+                    WindowRef {
+                        sender,
+                        object_path: path.to_string(),
+                        app_name: String::new(),
+                        title: String::new(),
+                    }
+                }
+            };
+            b_bindings.record_focus(window);
+
+            print!("(fswitcher) Bindings at {}:", Local::now().format("%H:%M:%S"));
+            for (product, window) in b_bindings.iter() {
+                match window {
+                    Some(w) => print!("\n\t{product}: {w}"),
+                    None => print!("\n\t{product}: None"),
+                }
             }
-            println!(
-                "(fswitcher) Bindings at {}:\n\t1: {}\n\t8195: {}",
-                Local::now().format("%H:%M:%S"),
-                b_bindings
-                    .get(&1)
-                    .and_then(|v| v.as_ref())
-                    .cloned()
-                    .unwrap_or_else(|| "None".into()),
-                b_bindings
-                    .get(&8195)
-                    .and_then(|v| v.as_ref())
-                    .cloned()
-                    .unwrap_or_else(|| "None".into())
-            );
+            println!();
         }
         // Other events (like Deactivate) are now silently ignored
     }
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Reference to a focused accessible window on the AT-SPI bus.
+///
+/// An accessible is addressed by the unique bus name that owns it together with
+/// its object path, so both are needed to later call methods (e.g. `GrabFocus`)
+/// against it. The resolved application and window names are cached alongside so
+/// the binding table is self-describing in logs and to any UI.
+#[derive(Clone, Debug)]
+pub struct WindowRef {
+    /// Unique bus name of the application that owns the accessible.
+    pub sender: String,
+    /// Object path of the window accessible.
+    pub object_path: String,
+    /// Name of the owning application (e.g. `"Firefox"`).
+    pub app_name: String,
+    /// Title of the focused window.
+    pub title: String,
+}
+
+impl fmt::Display for WindowRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.app_name.is_empty(), self.title.is_empty()) {
+            (true, true) => write!(f, "{}{}", self.sender, self.object_path),
+            (false, true) => write!(f, "{}", self.app_name),
+            (true, false) => write!(f, "{}", self.title),
+            (false, false) => write!(f, "{} — {}", self.app_name, self.title),
+        }
+    }
+}
+
+/// Per-keyboard window bindings, maintained as a push-down queue.
+///
+/// Each tracked keyboard (keyed by its libinput product ID) owns one slot that
+/// holds a reference to the window it should switch focus to. On every
+/// focus change the newly focused window is pushed onto the front of the queue
+/// and the existing bindings cascade down a slot, so the ordering always
+/// reflects most-recently-focused first.
+pub struct Bindings {
+    /// Product IDs in queue order; the front slot is the most recently focused.
+    order: Vec<u32>,
+    slots: HashMap<u32, Option<WindowRef>>,
+}
+
+impl Bindings {
+    /// Build a queue with one empty slot per configured product ID.
+    pub fn new(products: impl IntoIterator<Item = u32>) -> Self {
+        let order: Vec<u32> = products.into_iter().collect();
+        let slots = order.iter().map(|&p| (p, None)).collect();
+        Self { order, slots }
+    }
+
+    /// Allocate a fresh, empty slot for a keyboard that just appeared.
+    pub fn add(&mut self, product: u32) {
+        if !self.slots.contains_key(&product) {
+            self.order.push(product);
+            self.slots.insert(product, None);
+        }
+    }
+
+    /// Drop a disconnected keyboard's slot, collapsing the queue so the freed
+    /// window slot is not leaked.
+    pub fn remove(&mut self, product: u32) {
+        self.order.retain(|&p| p != product);
+        self.slots.remove(&product);
+    }
+
+    /// Push a newly focused window onto the front of the queue, cascading the
+    /// previous bindings down one slot each.
+    pub fn record_focus(&mut self, window: WindowRef) {
+        for i in (1..self.order.len()).rev() {
+            let prev = self.slots.get(&self.order[i - 1]).cloned().flatten();
+            self.slots.insert(self.order[i], prev);
+        }
+        if let Some(&front) = self.order.first() {
+            self.slots.insert(front, Some(window));
+        }
+    }
+
+    /// The window bound to a given keyboard, if any.
+    pub fn get(&self, product: u32) -> Option<&WindowRef> {
+        self.slots.get(&product).and_then(|v| v.as_ref())
+    }
+
+    /// Iterate the slots in queue order for logging.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, Option<&WindowRef>)> {
+        self.order
+            .iter()
+            .map(move |&p| (p, self.slots.get(&p).and_then(|v| v.as_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(path: &str) -> WindowRef {
+        WindowRef {
+            sender: ":1.0".into(),
+            object_path: path.into(),
+            app_name: String::new(),
+            title: String::new(),
+        }
+    }
+
+    #[test]
+    fn record_focus_cascades_through_slots() {
+        let mut bindings = Bindings::new([1, 2]);
+        bindings.record_focus(window("/a"));
+        assert_eq!(bindings.get(1).map(|w| w.object_path.as_str()), Some("/a"));
+        assert!(bindings.get(2).is_none());
+
+        // The next focus pushes /a down to the second slot.
+        bindings.record_focus(window("/b"));
+        assert_eq!(bindings.get(1).map(|w| w.object_path.as_str()), Some("/b"));
+        assert_eq!(bindings.get(2).map(|w| w.object_path.as_str()), Some("/a"));
+    }
+
+    #[test]
+    fn remove_frees_the_slot_without_leaking() {
+        let mut bindings = Bindings::new([1, 2]);
+        bindings.record_focus(window("/a"));
+        bindings.record_focus(window("/b"));
+        bindings.remove(1);
+        assert!(bindings.get(1).is_none());
+        // The surviving keyboard keeps its binding and a new focus lands on it.
+        bindings.record_focus(window("/c"));
+        assert_eq!(bindings.get(2).map(|w| w.object_path.as_str()), Some("/c"));
+    }
+
+    #[test]
+    fn add_is_idempotent() {
+        let mut bindings = Bindings::new([1]);
+        bindings.record_focus(window("/a"));
+        bindings.add(1); // already present; must not clobber the binding
+        assert_eq!(bindings.get(1).map(|w| w.object_path.as_str()), Some("/a"));
+    }
+}
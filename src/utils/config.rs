@@ -0,0 +1,55 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// A single keyboard fswitcher should track, identified by its USB-style
+/// vendor/product IDs as reported by libinput (`id_vendor`/`id_product`).
+#[derive(Clone, Debug, Deserialize)]
+pub struct KeyboardConfig {
+    pub vendor: u32,
+    pub product: u32,
+    /// Optional human-friendly label, only used for logging.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Runtime configuration, parsed from TOML at startup.
+///
+/// Mirrors the shape rusty-keys uses for `KeyMaps::from_cfg`: a flat document
+/// that declares the hardware to watch and the AT-SPI event classes to
+/// subscribe to, so nothing machine-specific has to live in the binary.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    pub keyboards: Vec<KeyboardConfig>,
+    /// AT-SPI event classes to register with the registry (e.g. `"object"`,
+    /// `"focus"`, `"window"`).
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+    /// Chord that triggers the window-switch action, e.g. `"Ctrl"` or
+    /// `"Super+grave"`. Parsed into a [`keys::Chord`](crate::utils::keys::Chord)
+    /// at startup.
+    #[serde(default = "default_trigger")]
+    pub trigger: String,
+    /// When true, configured keyboards are grabbed exclusively (`EVIOCGRAB`)
+    /// and their non-trigger events are re-injected through a virtual device so
+    /// the switch chord does not leak to the focused application.
+    #[serde(default)]
+    pub grab: bool,
+}
+
+fn default_events() -> Vec<String> {
+    vec!["object".into(), "focus".into(), "window".into()]
+}
+
+fn default_trigger() -> String {
+    "Ctrl".into()
+}
+
+impl Config {
+    /// Parse the configuration from a TOML file on disk.
+    pub fn from_cfg<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let raw = fs::read_to_string(path)?;
+        let cfg: Config = toml::from_str(&raw)?;
+        Ok(cfg)
+    }
+}
@@ -1,12 +1,178 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
-#[repr(u32)]
-pub enum Key {
-    LeftCtrl = 29,
-    RightCtrl = 97,
+use bitflags::bitflags;
+
+bitflags! {
+    /// The set of modifier keys currently held down.
+    ///
+    /// Left and right variants of a modifier collapse to the same bit, so a
+    /// chord matches regardless of which side was pressed. The set is updated
+    /// incrementally from each modifier press and release; it is not re-read
+    /// from the device, so a missed release leaves that modifier set until it
+    /// is released again.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Modifiers: u8 {
+        const CTRL = 0b0000_0001;
+        const ALT = 0b0000_0010;
+        const SHIFT = 0b0000_0100;
+        const SUPER = 0b0000_1000;
+    }
+}
+
+impl Modifiers {
+    /// Parse a modifier name as it may appear in a chord (case-insensitive).
+    pub fn from_token(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Some(Modifiers::CTRL),
+            "alt" => Some(Modifiers::ALT),
+            "shift" => Some(Modifiers::SHIFT),
+            "super" | "meta" | "win" => Some(Modifiers::SUPER),
+            _ => None,
+        }
+    }
+}
+
+// Table mapping libinput keycodes to symbolic names, and for modifier keys the
+// bit they toggle. Chords in the config file are expressed with these names.
+const KEYS: &[(u32, &str, Option<Modifiers>)] = &[
+    (1, "escape", None),
+    (14, "backspace", None),
+    (15, "tab", None),
+    (28, "enter", None),
+    (29, "ctrl", Some(Modifiers::CTRL)),     // left
+    (42, "shift", Some(Modifiers::SHIFT)),   // left
+    (54, "shift", Some(Modifiers::SHIFT)),   // right
+    (56, "alt", Some(Modifiers::ALT)),       // left
+    (57, "space", None),
+    (97, "ctrl", Some(Modifiers::CTRL)),     // right
+    (100, "alt", Some(Modifiers::ALT)),      // right
+    (125, "super", Some(Modifiers::SUPER)),  // left
+    (126, "super", Some(Modifiers::SUPER)),  // right
+    (41, "grave", None),
+    // letters
+    (30, "a", None),
+    (48, "b", None),
+    (46, "c", None),
+    (32, "d", None),
+    (18, "e", None),
+    (33, "f", None),
+    (34, "g", None),
+    (35, "h", None),
+    (23, "i", None),
+    (36, "j", None),
+    (37, "k", None),
+    (38, "l", None),
+    (50, "m", None),
+    (49, "n", None),
+    (24, "o", None),
+    (25, "p", None),
+    (16, "q", None),
+    (19, "r", None),
+    (31, "s", None),
+    (20, "t", None),
+    (22, "u", None),
+    (47, "v", None),
+    (17, "w", None),
+    (45, "x", None),
+    (21, "y", None),
+    (44, "z", None),
+];
+
+/// Look up the symbolic name of a keycode, if known.
+pub fn key_name(code: u32) -> Option<&'static str> {
+    KEYS.iter().find(|(c, ..)| *c == code).map(|(_, n, _)| *n)
+}
+
+/// Resolve a symbolic key name to its libinput keycode.
+pub fn key_code(name: &str) -> Option<u32> {
+    let name = name.to_ascii_lowercase();
+    KEYS.iter().find(|(_, n, _)| *n == name).map(|(c, ..)| *c)
+}
+
+/// The modifier a keycode toggles, if it is a modifier key.
+pub fn modifier_of(code: u32) -> Option<Modifiers> {
+    KEYS.iter().find(|(c, ..)| *c == code).and_then(|(.., m)| *m)
+}
+
+/// A trigger chord: a required modifier state plus an optional key whose press
+/// fires the action. A chord with no explicit key (e.g. `"Ctrl"`) fires when the
+/// modifier press itself completes the required set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub key: Option<u32>,
+}
+
+impl Chord {
+    /// Parse a chord such as `"Super+grave"` or `"Ctrl+Alt+t"`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut modifiers = Modifiers::empty();
+        let mut key = None;
+        for token in spec.split('+').map(str::trim).filter(|t| !t.is_empty()) {
+            if let Some(m) = Modifiers::from_token(token) {
+                modifiers |= m;
+            } else if let Some(code) = key_code(token) {
+                if key.is_some() {
+                    return Err(format!("chord '{spec}' has more than one trigger key"));
+                }
+                key = Some(code);
+            } else {
+                return Err(format!("unknown key '{token}' in chord '{spec}'"));
+            }
+        }
+        if modifiers.is_empty() && key.is_none() {
+            return Err(format!("chord '{spec}' is empty"));
+        }
+        Ok(Self { modifiers, key })
+    }
 }
 
-impl Key {
-    pub fn key(self) -> u32 {
-        self as u32
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_names_are_case_insensitive_with_aliases() {
+        assert_eq!(Modifiers::from_token("Ctrl"), Some(Modifiers::CTRL));
+        assert_eq!(Modifiers::from_token("control"), Some(Modifiers::CTRL));
+        assert_eq!(Modifiers::from_token("SUPER"), Some(Modifiers::SUPER));
+        assert_eq!(Modifiers::from_token("meta"), Some(Modifiers::SUPER));
+        assert_eq!(Modifiers::from_token("win"), Some(Modifiers::SUPER));
+        assert_eq!(Modifiers::from_token("nope"), None);
+    }
+
+    #[test]
+    fn left_and_right_modifiers_share_a_bit() {
+        assert_eq!(modifier_of(29), Some(Modifiers::CTRL)); // left
+        assert_eq!(modifier_of(97), Some(Modifiers::CTRL)); // right
+        assert_eq!(modifier_of(41), None); // grave is not a modifier
+    }
+
+    #[test]
+    fn parse_modifier_and_key_chord() {
+        let chord = Chord::parse("Super+grave").unwrap();
+        assert_eq!(chord.modifiers, Modifiers::SUPER);
+        assert_eq!(chord.key, key_code("grave"));
+    }
+
+    #[test]
+    fn parse_modifier_only_chord() {
+        let chord = Chord::parse("Ctrl").unwrap();
+        assert_eq!(chord.modifiers, Modifiers::CTRL);
+        assert_eq!(chord.key, None);
+    }
+
+    #[test]
+    fn reject_empty_chord() {
+        assert!(Chord::parse("").is_err());
+        assert!(Chord::parse("+").is_err());
+    }
+
+    #[test]
+    fn reject_unknown_key() {
+        assert!(Chord::parse("Ctrl+nosuchkey").is_err());
+    }
+
+    #[test]
+    fn reject_more_than_one_trigger_key() {
+        assert!(Chord::parse("a+b").is_err());
     }
 }
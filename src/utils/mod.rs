@@ -0,0 +1,4 @@
+pub mod bindings;
+pub mod config;
+pub mod keys;
+pub mod uinput;
@@ -0,0 +1,71 @@
+use input_linux::sys::{input_event, timeval};
+use input_linux::{EventKind, InputId, Key, UInputHandle};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+
+// Highest keycode we advertise on the virtual device; covers the full keyboard
+// range in linux/input-event-codes.h.
+const MAX_KEYCODE: u16 = 0x2ff;
+
+/// A `/dev/uinput`-backed virtual keyboard used to re-inject events read from a
+/// grabbed physical keyboard.
+///
+/// When fswitcher grabs a device with `EVIOCGRAB` the real events no longer
+/// reach applications, so every non-trigger key must be replayed through this
+/// device. The virtual device is torn down on drop so a crash can never leave a
+/// stray keyboard registered with the kernel.
+pub struct VirtualKeyboard {
+    handle: UInputHandle<File>,
+}
+
+impl VirtualKeyboard {
+    /// Create the virtual device, advertising the key and sync event types.
+    pub fn new() -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")?;
+        let handle = UInputHandle::new(file);
+
+        handle.set_evbit(EventKind::Key)?;
+        handle.set_evbit(EventKind::Synchronize)?;
+        for code in 0..=MAX_KEYCODE {
+            if let Ok(key) = Key::from_code(code) {
+                handle.set_keybit(key)?;
+            }
+        }
+
+        handle.create(&InputId::default(), b"fswitcher virtual keyboard", 0, &[])?;
+        Ok(Self { handle })
+    }
+
+    /// Replay a key event (`value` is 1 for press, 0 for release) followed by a
+    /// `SYN_REPORT` so the kernel dispatches it immediately.
+    pub fn emit(&self, code: u16, value: i32) -> io::Result<()> {
+        let events = [
+            event(EventKind::Key, code, value),
+            event(EventKind::Synchronize, 0, 0),
+        ];
+        self.handle.write(&events)?;
+        Ok(())
+    }
+}
+
+impl Drop for VirtualKeyboard {
+    fn drop(&mut self) {
+        let _ = self.handle.dev_destroy();
+    }
+}
+
+fn event(kind: EventKind, code: u16, value: i32) -> input_event {
+    input_event {
+        time: timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        type_: kind as u16,
+        code,
+        value,
+    }
+}